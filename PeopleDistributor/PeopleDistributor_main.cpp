@@ -15,12 +15,16 @@ int main()
     std::cout << "Starting program...\n";
 
     State s;
+    // Uncomment for reproducible runs
+    //s.set_random_seed(42);
     s.initialize(6, 6, 6, 6);
     std::vector<unsigned int> number_of_immovable_males_per_group{ 1,0,1,1,1,1 };
     std::vector<unsigned int> number_of_immovable_females_per_group{ 0,1,0,0,0,0 };
 
     s.add_number_of_immovable_males_per_group(number_of_immovable_males_per_group);
     s.add_number_of_immovable_females_per_group(number_of_immovable_females_per_group);
+    // Uncomment to continue from the result of the last run instead of a random state
+    //s.read_state_from_csv();
 
     s.print_state();
 
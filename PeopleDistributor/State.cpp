@@ -1,6 +1,18 @@
 #include "State.h"
 
 
+void State::shuffle_people(std::vector<unsigned int>& people, unsigned int first, 
+	xorshift128p_state* state)
+{
+	for (unsigned int i = static_cast<unsigned int>(people.size()); i > first + 1; --i) {
+		// Swap the last not yet shuffled person people[i - 1] with a random one of people[first] - people[i - 1]
+		unsigned int j = first + xorshift128p(state) % (i - first);
+		unsigned int temp = people[i - 1];
+		people[i - 1] = people[j];
+		people[j] = temp;
+	}
+}
+
 std::vector<unsigned int> State::create_male_numbers_vector(unsigned int total_males)
 {
 	std::vector<unsigned int> males(total_males, 0);
@@ -26,6 +38,18 @@ float State::average_contacts_per_person()
 		(number_of_males_per_group + number_of_females_per_group));
 }
 
+unsigned int State::upper_bound_of_contacts()
+{
+	unsigned int total_people = number_of_groups * (number_of_males_per_group + number_of_females_per_group);
+	unsigned int max_contacts_per_person = number_of_days * 
+		(number_of_males_per_group + number_of_females_per_group - 1);
+	if (max_contacts_per_person > total_people - 1) {
+		max_contacts_per_person = total_people - 1;
+	}
+	// Each contact is shared by two people
+	return (total_people * max_contacts_per_person) / 2;
+}
+
 int State::contact_delta_of_swap_m(unsigned int day, unsigned int male_group1, unsigned int male1, 
 	unsigned int male_group2, unsigned int male2)
 {
@@ -75,6 +99,23 @@ int State::contact_delta_of_swap_m(unsigned int day, unsigned int male_group1, u
 		}
 	}
 
+	// Calculate the changes of contacts with the females in both groups, who don't move.
+	// male1 leaves the females of male_group1 and meets the females of male_group2, male2 vice versa.
+	for (unsigned int female = 0; female < number_of_females_per_group; ++female) {
+		if (curr_contacts[f_day_group_person[day][male_group1][female]][male1_num] == 1) {
+			contact_delta--;
+		}
+		if (curr_contacts[f_day_group_person[day][male_group2][female]][male2_num] == 1) {
+			contact_delta--;
+		}
+		if (curr_contacts[f_day_group_person[day][male_group2][female]][male1_num] == 0) {
+			contact_delta++;
+		}
+		if (curr_contacts[f_day_group_person[day][male_group1][female]][male2_num] == 0) {
+			contact_delta++;
+		}
+	}
+
 	return contact_delta;
 }
 
@@ -127,6 +168,23 @@ int State::contact_delta_of_swap_f(unsigned int day, unsigned int female_group1,
 		}
 	}
 
+	// Calculate the changes of contacts with the males in both groups, who don't move.
+	// female1 leaves the males of female_group1 and meets the males of female_group2, female2 vice versa.
+	for (unsigned int male = 0; male < number_of_males_per_group; ++male) {
+		if (curr_contacts[m_day_group_person[day][female_group1][male]][female1_num] == 1) {
+			contact_delta--;
+		}
+		if (curr_contacts[m_day_group_person[day][female_group2][male]][female2_num] == 1) {
+			contact_delta--;
+		}
+		if (curr_contacts[m_day_group_person[day][female_group2][male]][female1_num] == 0) {
+			contact_delta++;
+		}
+		if (curr_contacts[m_day_group_person[day][female_group1][male]][female2_num] == 0) {
+			contact_delta++;
+		}
+	}
+
 	return contact_delta;
 }
 
@@ -200,6 +258,37 @@ void State::swap_m(unsigned int day, unsigned int male_group1, unsigned int male
 			curr_contacts[male2_num][m_day_group_person[day][male_group1][male_in_group1]]++;
 		}
 	}
+
+	// Update the contacts with the females in both groups, who don't move.
+	// male1 left the females of male_group1 and met the females of male_group2, male2 vice versa.
+	for (unsigned int female = 0; female < number_of_females_per_group; ++female) {
+		unsigned int female_in_group1_num = f_day_group_person[day][male_group1][female];
+		unsigned int female_in_group2_num = f_day_group_person[day][male_group2][female];
+
+		// Losses
+		if (curr_contacts[female_in_group1_num][male1_num] == 1) {
+			curr_num_contacts--;
+		}
+		curr_contacts[female_in_group1_num][male1_num]--;
+		curr_contacts[male1_num][female_in_group1_num]--;
+		if (curr_contacts[female_in_group2_num][male2_num] == 1) {
+			curr_num_contacts--;
+		}
+		curr_contacts[female_in_group2_num][male2_num]--;
+		curr_contacts[male2_num][female_in_group2_num]--;
+
+		// Gains
+		if (curr_contacts[female_in_group2_num][male1_num] == 0) {
+			curr_num_contacts++;
+		}
+		curr_contacts[female_in_group2_num][male1_num]++;
+		curr_contacts[male1_num][female_in_group2_num]++;
+		if (curr_contacts[female_in_group1_num][male2_num] == 0) {
+			curr_num_contacts++;
+		}
+		curr_contacts[female_in_group1_num][male2_num]++;
+		curr_contacts[male2_num][female_in_group1_num]++;
+	}
 }
 
 void State::swap_f(unsigned int day, unsigned int female_group1, unsigned int female1, 
@@ -272,6 +361,37 @@ void State::swap_f(unsigned int day, unsigned int female_group1, unsigned int fe
 			curr_contacts[female2_num][f_day_group_person[day][female_group1][female_in_group1]]++;
 		}
 	}
+
+	// Update the contacts with the males in both groups, who don't move.
+	// female1 left the males of female_group1 and met the males of female_group2, female2 vice versa.
+	for (unsigned int male = 0; male < number_of_males_per_group; ++male) {
+		unsigned int male_in_group1_num = m_day_group_person[day][female_group1][male];
+		unsigned int male_in_group2_num = m_day_group_person[day][female_group2][male];
+
+		// Losses
+		if (curr_contacts[male_in_group1_num][female1_num] == 1) {
+			curr_num_contacts--;
+		}
+		curr_contacts[male_in_group1_num][female1_num]--;
+		curr_contacts[female1_num][male_in_group1_num]--;
+		if (curr_contacts[male_in_group2_num][female2_num] == 1) {
+			curr_num_contacts--;
+		}
+		curr_contacts[male_in_group2_num][female2_num]--;
+		curr_contacts[female2_num][male_in_group2_num]--;
+
+		// Gains
+		if (curr_contacts[male_in_group2_num][female1_num] == 0) {
+			curr_num_contacts++;
+		}
+		curr_contacts[male_in_group2_num][female1_num]++;
+		curr_contacts[female1_num][male_in_group2_num]++;
+		if (curr_contacts[male_in_group1_num][female2_num] == 0) {
+			curr_num_contacts++;
+		}
+		curr_contacts[male_in_group1_num][female2_num]++;
+		curr_contacts[female2_num][male_in_group1_num]++;
+	}
 }
 
 void State::add_number_of_immovable_males_per_group(std::vector<unsigned int> number_of_immovable_males_per_group)
@@ -326,7 +446,83 @@ void State::try_random_female_swap_and_proceed_if_contact_delta_pos()
 	}
 }
 
-void State::perform_simulated_annealing_step(double temp)
+bool State::try_best_swap_on_random_day_and_proceed_if_contact_delta_pos()
+{
+	unsigned int day = (xorshift128p(&rnd_state) % (number_of_days - 1)) + 1;
+
+	// Only swaps that don't lose contacts are candidates. Ties between equally good swaps are
+	// broken at random (reservoir sampling), otherwise on a plateau the same zero gain swap
+	// would be made and undone over and over instead of exploring the plateau.
+	bool best_is_male = true;
+	int best_delta = -1;
+	unsigned int number_of_best_swaps = 0;
+	unsigned int best_group1 = 0, best_person1 = 0, best_group2 = 0, best_person2 = 0;
+
+	// Swaps within the same group never change anything, so only group1 < group2 is checked
+	for (unsigned int group1 = 0; group1 < number_of_groups; ++group1) {
+		for (unsigned int group2 = group1 + 1; group2 < number_of_groups; ++group2) {
+			for (unsigned int male1 = m_number_of_immovable_people_per_group[group1]; male1 < number_of_males_per_group; ++male1) {
+				for (unsigned int male2 = m_number_of_immovable_people_per_group[group2]; male2 < number_of_males_per_group; ++male2) {
+					int delta = contact_delta_of_swap_m(day, group1, male1, group2, male2);
+					if (delta < 0) {
+						continue;
+					}
+					if (delta > best_delta) {
+						best_delta = delta;
+						number_of_best_swaps = 0;
+					}
+					else if (delta < best_delta) {
+						continue;
+					}
+					number_of_best_swaps++;
+					if (xorshift128p(&rnd_state) % number_of_best_swaps == 0) {
+						best_is_male = true;
+						best_group1 = group1;
+						best_person1 = male1;
+						best_group2 = group2;
+						best_person2 = male2;
+					}
+				}
+			}
+			for (unsigned int female1 = f_number_of_immovable_people_per_group[group1]; female1 < number_of_females_per_group; ++female1) {
+				for (unsigned int female2 = f_number_of_immovable_people_per_group[group2]; female2 < number_of_females_per_group; ++female2) {
+					int delta = contact_delta_of_swap_f(day, group1, female1, group2, female2);
+					if (delta < 0) {
+						continue;
+					}
+					if (delta > best_delta) {
+						best_delta = delta;
+						number_of_best_swaps = 0;
+					}
+					else if (delta < best_delta) {
+						continue;
+					}
+					number_of_best_swaps++;
+					if (xorshift128p(&rnd_state) % number_of_best_swaps == 0) {
+						best_is_male = false;
+						best_group1 = group1;
+						best_person1 = female1;
+						best_group2 = group2;
+						best_person2 = female2;
+					}
+				}
+			}
+		}
+	}
+
+	if (best_delta >= 0) {
+		if (best_is_male) {
+			swap_m(day, best_group1, best_person1, best_group2, best_person2);
+		}
+		else {
+			swap_f(day, best_group1, best_person1, best_group2, best_person2);
+		}
+	}
+	return best_delta > 0;
+}
+
+void State::perform_simulated_annealing_step(double temp, unsigned long int& attempted_worse_swaps, 
+	unsigned long int& accepted_worse_swaps)
 {
 	unsigned int day = (xorshift128p(&rnd_state) % (number_of_days - 1)) + 1;
 	unsigned int male_group1 = xorshift128p(&rnd_state) % number_of_groups;
@@ -341,9 +537,13 @@ void State::perform_simulated_annealing_step(double temp)
 	if (delta_male >= 0) {
 		swap_m(day, male_group1, male1, male_group2, male2);
 	}
-	else if ((static_cast<double>(xorshift128p(&rnd_state)) / static_cast<double>(UINT64_MAX)) < 
-		exp(static_cast<double>(delta_male) / temp)) {
-		swap_m(day, male_group1, male1, male_group2, male2);
+	else {
+		attempted_worse_swaps++;
+		if ((static_cast<double>(xorshift128p(&rnd_state)) / static_cast<double>(UINT64_MAX)) < 
+			exp(static_cast<double>(delta_male) / temp)) {
+			swap_m(day, male_group1, male1, male_group2, male2);
+			accepted_worse_swaps++;
+		}
 	}
 
 	unsigned int female_group1 = xorshift128p(&rnd_state) % number_of_groups;
@@ -357,16 +557,59 @@ void State::perform_simulated_annealing_step(double temp)
 	if (delta_female >= 0) {
 		swap_f(day, female_group1, female1, female_group2, female2);
 	}
-	else if ((static_cast<double>(xorshift128p(&rnd_state)) / static_cast<double>(UINT64_MAX)) < 
-		exp(static_cast<double>(delta_female) / temp)) {
-		swap_f(day, female_group1, female1, female_group2, female2);
+	else {
+		attempted_worse_swaps++;
+		if ((static_cast<double>(xorshift128p(&rnd_state)) / static_cast<double>(UINT64_MAX)) < 
+			exp(static_cast<double>(delta_female) / temp)) {
+			swap_f(day, female_group1, female1, female_group2, female2);
+			accepted_worse_swaps++;
+		}
 	}
 }
 
+double State::average_contact_loss_of_random_swaps(unsigned int number_of_samples)
+{
+	long long total_loss = 0;
+	unsigned int number_of_losses = 0;
+	for (unsigned int sample = 0; sample < number_of_samples; ++sample) {
+		unsigned int day = (xorshift128p(&rnd_state) % (number_of_days - 1)) + 1;
+		unsigned int male_group1 = xorshift128p(&rnd_state) % number_of_groups;
+		unsigned int male_group2 = xorshift128p(&rnd_state) % number_of_groups;
+		unsigned int male1 = xorshift128p(&rnd_state) % (number_of_males_per_group - 
+			m_number_of_immovable_people_per_group[male_group1]) + m_number_of_immovable_people_per_group[male_group1];
+		unsigned int male2 = xorshift128p(&rnd_state) % (number_of_males_per_group - 
+			m_number_of_immovable_people_per_group[male_group2]) + m_number_of_immovable_people_per_group[male_group2];
+
+		int delta_male = contact_delta_of_swap_m(day, male_group1, male1, male_group2, male2);
+		if (delta_male < 0) {
+			total_loss -= delta_male;
+			number_of_losses++;
+		}
+
+		unsigned int female_group1 = xorshift128p(&rnd_state) % number_of_groups;
+		unsigned int female_group2 = xorshift128p(&rnd_state) % number_of_groups;
+		unsigned int female1 = xorshift128p(&rnd_state) % (number_of_females_per_group - 
+			f_number_of_immovable_people_per_group[female_group1]) + f_number_of_immovable_people_per_group[female_group1];
+		unsigned int female2 = xorshift128p(&rnd_state) % (number_of_females_per_group - 
+			f_number_of_immovable_people_per_group[female_group2]) + f_number_of_immovable_people_per_group[female_group2];
+
+		int delta_female = contact_delta_of_swap_f(day, female_group1, female1, female_group2, female2);
+		if (delta_female < 0) {
+			total_loss -= delta_female;
+			number_of_losses++;
+		}
+	}
+	if (number_of_losses == 0) {
+		return 0.0;
+	}
+	return static_cast<double>(total_loss) / static_cast<double>(number_of_losses);
+}
+
 State::State(){
 	//rnd_state = new xorshift128p_state();
 	rnd_state.a = std::time(0);
 	rnd_state.b = 1234124124;
+	initial_state_seed = std::random_device()();
 }
 
 State::State(unsigned int in_number_of_groups, unsigned int in_number_of_males_per_group,
@@ -374,6 +617,7 @@ State::State(unsigned int in_number_of_groups, unsigned int in_number_of_males_p
 {
 	rnd_state.a = std::time(0);
 	rnd_state.b = 1234124124;
+	initial_state_seed = std::random_device()();
 	initialize(in_number_of_groups, in_number_of_males_per_group, in_number_of_females_per_group, in_number_of_days);
 }
 
@@ -381,6 +625,13 @@ State::~State()
 {
 }
 
+void State::set_random_seed(uint64_t seed)
+{
+	initial_state_seed = seed;
+	rnd_state.a = seed;
+	rnd_state.b = 1234124124;
+}
+
 void State::initialize(unsigned int in_number_of_groups, unsigned int in_number_of_males_per_group, 
 	unsigned int in_number_of_females_per_group, unsigned int in_number_of_days)
 {
@@ -389,7 +640,6 @@ void State::initialize(unsigned int in_number_of_groups, unsigned int in_number_
 	number_of_females_per_group = in_number_of_females_per_group;
 	number_of_days = in_number_of_days;
 
-	unsigned int total_people = number_of_groups * (number_of_males_per_group + number_of_females_per_group);
 	unsigned int total_males = number_of_groups * number_of_males_per_group;
 	unsigned int total_females = number_of_groups * number_of_females_per_group;
 
@@ -397,9 +647,6 @@ void State::initialize(unsigned int in_number_of_groups, unsigned int in_number_
 	std::vector<unsigned int> f_number_of_immovable_people_per_group(number_of_groups, 0);
 
 
-	std::vector<std::vector<unsigned int>> vec_curr_c (total_people, std::vector<unsigned int> (total_people, 0));
-	curr_contacts = vec_curr_c;
-
 	std::vector<std::vector<std::vector<unsigned int>>>
 		vec_m(number_of_days, std::vector<std::vector<unsigned int>>
 			(number_of_groups, std::vector<unsigned int>
@@ -424,6 +671,11 @@ void State::initialize(unsigned int in_number_of_groups, unsigned int in_number_
 	// In the first layer, they are going to appear in order, in the other layers they
 	// will be mixed randomly.
 
+	// The initial state gets its own generator so it doesn't share the sequence of rnd_state
+	xorshift128p_state init_rnd_state;
+	init_rnd_state.a = initial_state_seed;
+	init_rnd_state.b = 0x9E3779B97F4A7C15;
+
 	// Starting with m_day_group_person:
 	// Males will be represented by the integers 0 - total_males - 1.
 	std::vector<unsigned int> males;
@@ -443,11 +695,9 @@ void State::initialize(unsigned int in_number_of_groups, unsigned int in_number_
 	for (unsigned int day = 1; day < number_of_days; ++day) {	// for each layer
 		males = create_male_numbers_vector(total_males);
 		// shuffle the vector randomly
-		std::random_device random_dev;
-		std::mt19937       generator(random_dev());
 		// Ugly: doesn't shuffle the parts there the immovable persons sit
 		// @@@@@@@@@@@@@@ HARD CODED TO INITIALIZE PARAMETERS FOR THIS PROBLEM!!! @@@@@@@@@@@@@@@@@
-		std::shuffle(males.begin()+6, males.end(), generator);
+		shuffle_people(males, 6, &init_rnd_state);
 		for (unsigned int person = 0; person < number_of_males_per_group; ++person) { // for each column
 			for (unsigned int group = 0; group < number_of_groups; ++group) {   // for each row
 			
@@ -475,11 +725,9 @@ void State::initialize(unsigned int in_number_of_groups, unsigned int in_number_
 	for (unsigned int day = 1; day < number_of_days; ++day) {	// for each layer
 		females = create_female_numbers_vector(total_females, total_males);
 		// shuffle the vector randomly
-		std::random_device random_dev;
-		std::mt19937       generator(random_dev());
 		// Ugly: doesn't shuffle the parts there the immovable persons sit
 		// @@@@@@@@@@@@@@ HARD CODED TO INITIALIZE PARAMETERS FOR THIS PROBLEM!!! @@@@@@@@@@@@@@@@@
-		std::shuffle(females.begin()+2, females.end(), generator);
+		shuffle_people(females, 2, &init_rnd_state);
 		for (unsigned int person = 0; person < number_of_females_per_group; ++person) { // for each column
 		for (unsigned int group = 0; group < number_of_groups; ++group) {   // for each row
 
@@ -490,7 +738,14 @@ void State::initialize(unsigned int in_number_of_groups, unsigned int in_number_
 	}
 
 	// Now the state is randomly initialized and only the contacts matrix must be still updated.
-	// This can easily be done in a loop:
+	calculate_contacts_from_state();
+}
+
+void State::calculate_contacts_from_state()
+{
+	unsigned int total_people = number_of_groups * (number_of_males_per_group + number_of_females_per_group);
+	std::vector<std::vector<unsigned int>> vec_curr_c(total_people, std::vector<unsigned int>(total_people, 0));
+	curr_contacts = vec_curr_c;
 	curr_num_contacts = 0;
 	bool new_contact;
 
@@ -549,6 +804,139 @@ void State::initialize(unsigned int in_number_of_groups, unsigned int in_number_
 	}
 }
 
+void State::read_state_from_csv(std::string filename)
+{
+	std::ifstream icsv;
+	icsv.open(filename);
+	if (!icsv.is_open()) {
+		throw std::runtime_error("Could not open " + filename + " to read the state from!");
+	}
+	// The file has the format written by write_state_to_csv: for every day first the rows 
+	// of males, then the rows of females, each row having one number per group, and the
+	// days separated by an empty line.
+	unsigned int total_males = number_of_groups * number_of_males_per_group;
+	unsigned int total_people = number_of_groups * (number_of_males_per_group + number_of_females_per_group);
+	unsigned int rows_per_day = number_of_males_per_group + number_of_females_per_group;
+	std::string parameter_hint = " (initialize must be called with the same parameters the file was written with)!";
+
+	std::vector<std::vector<std::vector<unsigned int>>> day_row_group;
+	bool day_finished = true;
+	std::string line;
+	unsigned int line_number = 0;
+	while (std::getline(icsv, line)) {
+		line_number++;
+		std::string line_location = " in line " + std::to_string(line_number) + " of " + filename;
+		if (line.find_first_not_of(" \t\r") == std::string::npos) {
+			day_finished = true;
+			continue;
+		}
+		if (day_finished) {
+			day_row_group.push_back(std::vector<std::vector<unsigned int>>());
+			day_finished = false;
+		}
+
+		std::vector<unsigned int> row;
+		std::stringstream line_stream(line);
+		std::string cell;
+		while (std::getline(line_stream, cell, ',')) {
+			size_t first = cell.find_first_not_of(" \t\r");
+			if (first == std::string::npos) {
+				// The trailing comma of write_state_to_csv leaves an empty last cell
+				continue;
+			}
+			cell = cell.substr(first, cell.find_last_not_of(" \t\r") - first + 1);
+			// std::stoul would accept "12abc" as 12 and wrap "-1" around, so check everything
+			size_t pos = 0;
+			unsigned long value = 0;
+			try {
+				value = std::stoul(cell, &pos);
+			}
+			catch (const std::exception&) {
+				pos = 0;
+			}
+			if (cell[0] < '0' || cell[0] > '9' || pos != cell.size() || value >= total_people) {
+				throw std::runtime_error("Invalid entry \"" + cell + "\"" + line_location + 
+					", expected a person number from 0 to " + std::to_string(total_people - 1) + "!");
+			}
+			row.push_back(static_cast<unsigned int>(value));
+		}
+		if (row.size() != number_of_groups) {
+			throw std::runtime_error("Found " + std::to_string(row.size()) + " entries" + line_location + 
+				", but there are " + std::to_string(number_of_groups) + " groups" + parameter_hint);
+		}
+		if (day_row_group.back().size() == rows_per_day) {
+			throw std::runtime_error("Day " + std::to_string(day_row_group.size() - 1) + " has more than " + 
+				std::to_string(rows_per_day) + " rows" + line_location + parameter_hint);
+		}
+		day_row_group.back().push_back(row);
+	}
+	icsv.close();
+
+	if (day_row_group.size() != number_of_days) {
+		throw std::runtime_error(filename + " contains " + std::to_string(day_row_group.size()) + 
+			" days, but the state has " + std::to_string(number_of_days) + parameter_hint);
+	}
+
+	// Only overwrite the state once the whole file is known to be valid
+	std::vector<std::vector<std::vector<unsigned int>>> new_m_day_group_person(m_day_group_person);
+	std::vector<std::vector<std::vector<unsigned int>>> new_f_day_group_person(f_day_group_person);
+	for (unsigned int day = 0; day < number_of_days; ++day) {
+		if (day_row_group[day].size() != rows_per_day) {
+			throw std::runtime_error("Day " + std::to_string(day) + " in " + filename + " has " + 
+				std::to_string(day_row_group[day].size()) + " rows, but needs " + std::to_string(rows_per_day) + 
+				" (males + females per group)" + parameter_hint);
+		}
+		std::vector<unsigned int> seen(total_people, 0);
+		for (unsigned int male = 0; male < number_of_males_per_group; ++male) {
+			for (unsigned int group = 0; group < number_of_groups; ++group) {
+				unsigned int person = day_row_group[day][male][group];
+				if (person >= total_males || seen[person]++ > 0) {
+					throw std::runtime_error("Invalid or duplicate male " + std::to_string(person) + 
+						" on day " + std::to_string(day) + " in " + filename + "!");
+				}
+				new_m_day_group_person[day][group][male] = person;
+			}
+		}
+		for (unsigned int female = 0; female < number_of_females_per_group; ++female) {
+			for (unsigned int group = 0; group < number_of_groups; ++group) {
+				unsigned int person = day_row_group[day][number_of_males_per_group + female][group];
+				if (person < total_males || seen[person]++ > 0) {
+					throw std::runtime_error("Invalid or duplicate female " + std::to_string(person) + 
+						" on day " + std::to_string(day) + " in " + filename + "!");
+				}
+				new_f_day_group_person[day][group][female] = person;
+			}
+		}
+	}
+
+	// The immovable people must sit in the same place on every day, otherwise the swaps
+	// (which never touch the immovable slots) couldn't ever fix that
+	for (unsigned int day = 1; day < number_of_days; ++day) {
+		for (unsigned int group = 0; group < m_number_of_immovable_people_per_group.size(); ++group) {
+			for (unsigned int male = 0; male < m_number_of_immovable_people_per_group[group]; ++male) {
+				if (new_m_day_group_person[day][group][male] != new_m_day_group_person[0][group][male]) {
+					throw std::runtime_error("Immovable male slot " + std::to_string(male) + " of group " + 
+						std::to_string(group) + " differs between day 0 and day " + std::to_string(day) + " in " + 
+						filename + " (add the immovable people with the same settings the file was written with)!");
+				}
+			}
+		}
+		for (unsigned int group = 0; group < f_number_of_immovable_people_per_group.size(); ++group) {
+			for (unsigned int female = 0; female < f_number_of_immovable_people_per_group[group]; ++female) {
+				if (new_f_day_group_person[day][group][female] != new_f_day_group_person[0][group][female]) {
+					throw std::runtime_error("Immovable female slot " + std::to_string(female) + " of group " + 
+						std::to_string(group) + " differs between day 0 and day " + std::to_string(day) + " in " + 
+						filename + " (add the immovable people with the same settings the file was written with)!");
+				}
+			}
+		}
+	}
+	m_day_group_person = new_m_day_group_person;
+	f_day_group_person = new_f_day_group_person;
+
+	calculate_contacts_from_state();
+}
+
 void State::print_number_of_contacts_per_person()
 {
 	std::cout << "Average contacts per person in the current state: " << average_contacts_per_person() << std::endl;
@@ -559,11 +947,114 @@ void State::print_total_number_of_contacts()
 	std::cout << "Total contacts in the current state: " << curr_num_contacts << std::endl;
 }
 
+void State::print_upper_bound_of_contacts()
+{
+	std::cout << "Upper bound of total contacts: " << upper_bound_of_contacts() << std::endl;
+}
+
 void State::print_random_number()
 {
 	std::cout << "Random number: " << (xorshift128p(&rnd_state)%6)+1 << std::endl;
 }
 
+bool State::verify_state()
+{
+	unsigned int total_people = number_of_groups * (number_of_males_per_group + number_of_females_per_group);
+	unsigned int total_males = number_of_groups * number_of_males_per_group;
+
+	// Check 1: every person is in exactly one group on every day
+	bool schedule_ok = true;
+	for (unsigned int day = 0; day < number_of_days; ++day) {
+		std::vector<unsigned int> seen(total_people, 0);
+		for (unsigned int group = 0; group < number_of_groups; ++group) {
+			for (unsigned int male = 0; male < number_of_males_per_group; ++male) {
+				unsigned int person = m_day_group_person[day][group][male];
+				if (person >= total_males) {
+					schedule_ok = false;
+				}
+				else {
+					seen[person]++;
+				}
+			}
+			for (unsigned int female = 0; female < number_of_females_per_group; ++female) {
+				unsigned int person = f_day_group_person[day][group][female];
+				if (person < total_males || person >= total_people) {
+					schedule_ok = false;
+				}
+				else {
+					seen[person]++;
+				}
+			}
+		}
+		for (unsigned int person = 0; person < total_people; ++person) {
+			if (seen[person] != 1) {
+				schedule_ok = false;
+			}
+		}
+	}
+
+	// Check 2: the immovable people are still in the same place as on the first day
+	bool immovable_ok = true;
+	for (unsigned int day = 1; day < number_of_days; ++day) {
+		for (unsigned int group = 0; group < m_number_of_immovable_people_per_group.size(); ++group) {
+			for (unsigned int male = 0; male < m_number_of_immovable_people_per_group[group]; ++male) {
+				if (m_day_group_person[day][group][male] != m_day_group_person[0][group][male]) {
+					immovable_ok = false;
+				}
+			}
+		}
+		for (unsigned int group = 0; group < f_number_of_immovable_people_per_group.size(); ++group) {
+			for (unsigned int female = 0; female < f_number_of_immovable_people_per_group[group]; ++female) {
+				if (f_day_group_person[day][group][female] != f_day_group_person[0][group][female]) {
+					immovable_ok = false;
+				}
+			}
+		}
+	}
+
+	// Check 3 and 4: the contact matrix and the number of contacts match the schedule.
+	// The diagonal is ignored because it doesn't matter (see State.h).
+	std::vector<std::vector<unsigned int>> contacts(total_people, std::vector<unsigned int>(total_people, 0));
+	if (schedule_ok) {
+		for (unsigned int day = 0; day < number_of_days; ++day) {
+			for (unsigned int group = 0; group < number_of_groups; ++group) {
+				std::vector<unsigned int> members(m_day_group_person[day][group]);
+				members.insert(members.end(), f_day_group_person[day][group].begin(), 
+					f_day_group_person[day][group].end());
+				for (unsigned int person1 : members) {
+					for (unsigned int person2 : members) {
+						if (person1 != person2) {
+							contacts[person1][person2]++;
+						}
+					}
+				}
+			}
+		}
+	}
+	bool contact_matrix_ok = schedule_ok;
+	int num_contacts = 0;
+	for (unsigned int person1 = 0; person1 < total_people; ++person1) {
+		for (unsigned int person2 = person1 + 1; person2 < total_people; ++person2) {
+			if (contacts[person1][person2] != curr_contacts[person1][person2] || 
+				contacts[person2][person1] != curr_contacts[person2][person1]) {
+				contact_matrix_ok = false;
+			}
+			if (contacts[person1][person2] > 0) {
+				num_contacts++;
+			}
+		}
+	}
+	bool num_contacts_ok = schedule_ok && num_contacts == curr_num_contacts;
+
+	std::cout << "Verification of the current state:" << std::endl;
+	std::cout << "  Every person in exactly one group per day: " << (schedule_ok ? "pass" : "FAIL") << std::endl;
+	std::cout << "  Immovable people unchanged: " << (immovable_ok ? "pass" : "FAIL") << std::endl;
+	std::cout << "  Contact matrix matches schedule: " << (contact_matrix_ok ? "pass" : "FAIL") << std::endl;
+	std::cout << "  Total contacts match schedule: " << (num_contacts_ok ? "pass" : "FAIL") << std::endl;
+
+	return schedule_ok && immovable_ok && contact_matrix_ok && num_contacts_ok;
+}
+
 void State::print_state()
 {
 	for (unsigned int day = 0; day < number_of_days; ++day) {
@@ -605,6 +1096,81 @@ void State::write_state_to_csv()
 	ocsv.close();
 }
 
+void State::write_state_to_long_csv()
+{
+	unsigned int total_people = number_of_groups * (number_of_males_per_group + number_of_females_per_group);
+	unsigned int total_males = number_of_groups * number_of_males_per_group;
+
+	// How often each pair met in the sessions before the current one
+	std::vector<std::vector<unsigned int>> met_before(total_people, std::vector<unsigned int>(total_people, 0));
+
+	std::ofstream ocsv;
+	ocsv.open("Data_long.csv");
+	ocsv << "session,group,person,sex,new_contacts,repeats\n";
+	for (unsigned int day = 0; day < number_of_days; ++day) {
+		std::vector<std::vector<unsigned int>> group_members(number_of_groups);
+		for (unsigned int group = 0; group < number_of_groups; ++group) {
+			group_members[group] = m_day_group_person[day][group];
+			group_members[group].insert(group_members[group].end(), f_day_group_person[day][group].begin(), 
+				f_day_group_person[day][group].end());
+			std::sort(group_members[group].begin(), group_members[group].end());
+
+			for (unsigned int person1 : group_members[group]) {
+				unsigned int new_contacts = 0;
+				unsigned int repeats = 0;
+				for (unsigned int person2 : group_members[group]) {
+					if (person1 == person2) {
+						continue;
+					}
+					if (met_before[person1][person2] == 0) {
+						new_contacts++;
+					}
+					else {
+						repeats++;
+					}
+				}
+				ocsv << day << "," << group << "," << person1 << "," << (person1 < total_males ? "m" : "f") << "," 
+					<< new_contacts << "," << repeats << "\n";
+			}
+		}
+		// Only now count this session's meetings, so they don't influence the rows of this session
+		for (unsigned int group = 0; group < number_of_groups; ++group) {
+			for (unsigned int person1 : group_members[group]) {
+				for (unsigned int person2 : group_members[group]) {
+					if (person1 != person2) {
+						met_before[person1][person2]++;
+					}
+				}
+			}
+		}
+	}
+	ocsv.close();
+}
+
+void State::write_contact_graph_to_dot()
+{
+	unsigned int total_people = number_of_groups * (number_of_males_per_group + number_of_females_per_group);
+	unsigned int total_males = number_of_groups * number_of_males_per_group;
+
+	std::ofstream odot;
+	odot.open("Contacts.dot");
+	odot << "graph contacts {\n";
+	for (unsigned int person = 0; person < total_people; ++person) {
+		odot << "\t" << person << " [sex=\"" << (person < total_males ? "m" : "f") << "\"];\n";
+	}
+	// The matrix is symmetrical, so only the upper triangle is needed for the edges
+	for (unsigned int person1 = 0; person1 < total_people; ++person1) {
+		for (unsigned int person2 = person1 + 1; person2 < total_people; ++person2) {
+			if (curr_contacts[person1][person2] > 0) {
+				odot << "\t" << person1 << " -- " << person2 << " [weight=" << curr_contacts[person1][person2] 
+					<< ", type=\"" << (curr_contacts[person1][person2] == 1 ? "new" : "repeated") << "\"];\n";
+			}
+		}
+	}
+	odot << "}\n";
+	odot.close();
+}
+
 double State::random()
 {
 	return static_cast<double>(xorshift128p(&rnd_state))/ static_cast<double>(UINT64_MAX);
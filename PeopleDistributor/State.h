@@ -6,6 +6,9 @@
 #include <stdint.h>
 #include <ctime>
 #include <fstream>
+#include <sstream>
+#include <string>
+#include <algorithm>
 
 
 class State
@@ -20,6 +23,10 @@ private:
 
 	xorshift128p_state rnd_state;
 
+	// Seed of the generator that scrambles the initial state in initialize().
+	// Taken from std::random_device unless set_random_seed() is called.
+	uint64_t initial_state_seed;
+
 	// The state must be seeded so that it is not all zero
 	uint64_t xorshift128p(struct xorshift128p_state* state)
 	{
@@ -33,6 +40,12 @@ private:
 		return t + s;
 	}
 
+	// Fisher-Yates shuffle of people[first] - people[people.size() - 1] with the xorshift generator.
+	// Used instead of std::shuffle, whose algorithm differs between standard libraries
+	// (MSVC, libstdc++, ...) so the same seed would give different initial states.
+	void shuffle_people(std::vector<unsigned int>& people, unsigned int first, 
+		xorshift128p_state* state);
+
 	// These are the 4 main variables defined during the creation of the object
 	// every "day" the groups get redistributed
 	unsigned int number_of_groups;
@@ -82,8 +95,17 @@ private:
 	// Just a variable storing the result of the target function for the current state.
 	int curr_num_contacts;
 
+	// Rebuilds curr_contacts and curr_num_contacts from scratch for the schedule
+	// currently stored in m_day_group_person and f_day_group_person.
+	void calculate_contacts_from_state();
+
 	float average_contacts_per_person();
 
+	// Theoretical maximum of curr_num_contacts: every person can meet at most
+	// (group size - 1) other people per day, and never more than everyone else.
+	// This is much tighter than n*(n-1)/2 for small groups or few days.
+	unsigned int upper_bound_of_contacts();
+
 	// These methods return how the target function would change if two m or f persons
 	// would swap groups on a certain day. The simulated annealing algorithm needs to know this 
 	// to decide whether or not to execute the swap.
@@ -121,21 +143,69 @@ public:
 	void initialize(unsigned int number_of_groups, unsigned int number_of_males_per_group,
 		unsigned int number_of_females_per_group, unsigned int number_of_days);
 
+	// Makes runs reproducible: the same seed and parameters always yield the same initial
+	// state on every platform, and the same run on the same build. Across compilers the runs
+	// can still diverge, because the acceptance decisions use exp() and pow() from the
+	// platform math library, which may differ in the last bit between e.g. MSVC and glibc.
+	// Must be called BEFORE initialize() to affect the initial state.
+	void set_random_seed(uint64_t seed);
+
 	void add_number_of_immovable_males_per_group(std::vector<unsigned int> number_of_immovable_males_per_group);
 	void add_number_of_immovable_females_per_group(std::vector<unsigned int> number_of_immovable_females_per_group);
 
 	void try_random_male_swap_and_proceed_if_contact_delta_pos();
 	void try_random_female_swap_and_proceed_if_contact_delta_pos();
 
-	void perform_simulated_annealing_step(double temp);
+	// Steepest ascent: evaluates every possible male and female swap on a random day
+	// (except the first) and executes the best one if it doesn't lose contacts, picking
+	// randomly between equally good swaps. Returns true if the executed swap gained contacts.
+	bool try_best_swap_on_random_day_and_proceed_if_contact_delta_pos();
+
+	// Adds how many of the attempted swaps would reduce the number of contacts to 
+	// attempted_worse_swaps and how many of those were accepted to accepted_worse_swaps,
+	// which the adaptive cooling schedule uses to detect a frozen state.
+	void perform_simulated_annealing_step(double temp, unsigned long int& attempted_worse_swaps, 
+		unsigned long int& accepted_worse_swaps);
+
+	// Samples number_of_samples random male and female swaps (without executing them)
+	// and returns the average amount by which the swaps that would lose contacts lose them.
+	// Returns 0 if none of the sampled swaps would lose contacts.
+	// Used to calibrate the temperatures of the simulated annealing algorithm.
+	double average_contact_loss_of_random_swaps(unsigned int number_of_samples);
 
 	void print_number_of_contacts_per_person();
 	void print_total_number_of_contacts();
+	void print_upper_bound_of_contacts();
 	void print_random_number();
 
+	// Recomputes everything from the schedule alone and compares it to the
+	// incrementally tracked values. Prints pass/fail per check, returns true
+	// if all checks passed.
+	bool verify_state();
+
 	void print_state();
 	void write_state_to_csv();
 
+	// Writes the schedule in long format to Data_long.csv, one row per person per day:
+	// session,group,person,sex,new_contacts,repeats
+	// new_contacts are the group mates the person meets for the first time in that session,
+	// repeats the ones they already met in an earlier session.
+	// Rows are ordered by session, group and person number, so the output is deterministic.
+	void write_state_to_long_csv();
+
+	// Writes the final contact network to Contacts.dot (Graphviz DOT, can be opened in Gephi):
+	// one node per person with their sex, one edge per pair that met, weighted by how often
+	// they met and flagged as "new" (met once) or "repeated" (met more than once).
+	void write_contact_graph_to_dot();
+
+	// Warm start: replaces the schedule with one previously written by write_state_to_csv,
+	// e.g. to continue optimizing the result of an earlier run.
+	// Must be called after initialize() with the same parameters the file was written with,
+	// and after the immovable people were added: files in which an immovable slot doesn't hold
+	// the same person on every day are rejected. Throws a std::runtime_error for any file that
+	// doesn't match the state, without changing the state.
+	void read_state_from_csv(std::string filename = "Data.csv");
+
 	double random();
 	
 
@@ -1,39 +1,166 @@
 #include "subroutines.h"
 
-void run_random_hillclimbing_algorithm(State hill_climbing, unsigned int number_of_iterations) {
+enum class CoolingSchedule { geometric, linear, exponential, logarithmic, adaptive };
+
+CoolingSchedule parse_cooling_schedule(std::string cooling_schedule) {
+    if (cooling_schedule == "geometric") {
+        return CoolingSchedule::geometric;
+    }
+    if (cooling_schedule == "linear") {
+        return CoolingSchedule::linear;
+    }
+    if (cooling_schedule == "exponential") {
+        return CoolingSchedule::exponential;
+    }
+    if (cooling_schedule == "logarithmic") {
+        return CoolingSchedule::logarithmic;
+    }
+    if (cooling_schedule == "adaptive") {
+        return CoolingSchedule::adaptive;
+    }
+    throw std::runtime_error("Unknown cooling schedule \"" + cooling_schedule + "\". Supported cooling "
+        "schedules are: geometric, linear, exponential, logarithmic, adaptive");
+}
+
+enum class HillClimbingVariant { first_improvement, steepest };
+
+HillClimbingVariant parse_hill_climbing_variant(std::string variant) {
+    if (variant == "first_improvement") {
+        return HillClimbingVariant::first_improvement;
+    }
+    if (variant == "steepest") {
+        return HillClimbingVariant::steepest;
+    }
+    throw std::runtime_error("Unknown hill climbing variant \"" + variant + "\". Supported hill climbing "
+        "variants are: first_improvement, steepest");
+}
+
+void run_random_hillclimbing_algorithm(State hill_climbing, unsigned int number_of_iterations,
+    std::string variant) {
+    HillClimbingVariant hill_climbing_variant = parse_hill_climbing_variant(variant);
+
     std::cout << "Total number of contacts in initial state for hill climbing:\n";
     hill_climbing.print_total_number_of_contacts();
     hill_climbing.print_number_of_contacts_per_person();
+    hill_climbing.print_upper_bound_of_contacts();
+    std::cout << "Hill climbing variant: " << variant << std::endl;
+    unsigned long int number_of_gaining_iterations = 0;
     for (unsigned long int i = 0; i < number_of_iterations; ++i) {
-        hill_climbing.try_random_male_swap_and_proceed_if_contact_delta_pos();
-        hill_climbing.try_random_female_swap_and_proceed_if_contact_delta_pos();
+        if (hill_climbing_variant == HillClimbingVariant::steepest) {
+            if (hill_climbing.try_best_swap_on_random_day_and_proceed_if_contact_delta_pos()) {
+                number_of_gaining_iterations++;
+            }
+        }
+        else {
+            hill_climbing.try_random_male_swap_and_proceed_if_contact_delta_pos();
+            hill_climbing.try_random_female_swap_and_proceed_if_contact_delta_pos();
+        }
     }
     std::cout << "Total number of contacts after " << number_of_iterations << 
         " steps of some random hillclimbing:\n";
     hill_climbing.print_total_number_of_contacts();
     hill_climbing.print_number_of_contacts_per_person();
+    if (hill_climbing_variant == HillClimbingVariant::steepest) {
+        std::cout << "Iterations that gained contacts: " << number_of_gaining_iterations << std::endl;
+    }
 }
 
-void run_simulated_annealing_algorithm(State simulated_annealing, unsigned int number_of_iterations) {
+void run_simulated_annealing_algorithm(State simulated_annealing, unsigned int number_of_iterations,
+    std::string cooling_schedule, bool auto_temperature) {
+    // Parsed once here, so the hot loop doesn't compare strings
+    CoolingSchedule schedule = parse_cooling_schedule(cooling_schedule);
+
     std::cout << "Total number of contacts in initial state for simulated annealing:\n";
     simulated_annealing.print_total_number_of_contacts();
     simulated_annealing.print_number_of_contacts_per_person();
+    simulated_annealing.print_upper_bound_of_contacts();
     double t_start = 1000.0;
     double t_end = 0.001;
+
+    if (auto_temperature) {
+        // Acceptance probability of a swap losing d contacts is exp(-d / temp), so
+        // temp = -d / log(acceptance probability).
+        // The end temperature is based on the smallest possible loss of 1 contact, otherwise
+        // small losses would still be accepted often at the end and the result would be noisy.
+        double average_loss = simulated_annealing.average_contact_loss_of_random_swaps(500);
+        if (average_loss > 0.0) {
+            t_start = -average_loss / log(0.8);
+            t_end = -1.0 / log(0.01);
+        }
+        else {
+            std::cout << "No sampled swap loses contacts, keeping the default temperatures." << std::endl;
+        }
+    }
     double temp = t_start;
 
     double lambda = pow(t_start / t_end, 1.0 / static_cast<double>(number_of_iterations));
+    double log_factor = (t_start / t_end - 1.0) / log(1.0 + static_cast<double>(number_of_iterations));
+    double exponential_rate = 10.0;
+    double exponential_offset = exp(-exponential_rate);
+
+    // The adaptive schedule looks at the acceptance rate of worsening swaps in windows of
+    // 1% of the iterations. Below the frozen rate the temperature is multiplied by the
+    // reheat factor and lambda is recalculated so t_end is still reached at the end.
+    unsigned long int adaptive_window = number_of_iterations / 100 > 0 ? number_of_iterations / 100 : 1;
+    double adaptive_frozen_rate = 0.0001;
+    double adaptive_reheat_factor = 10.0;
+    unsigned long int attempted_worse_swaps_in_window = 0;
+    unsigned long int accepted_worse_swaps_in_window = 0;
+    unsigned int number_of_reheats = 0;
 
+    std::cout << "Cooling schedule: " << cooling_schedule << std::endl;
     std::cout << "Starting temperature: " << temp << std::endl;
-    std::cout << "Temperature reduction factor lambda: " << lambda << std::endl;
+    if (schedule == CoolingSchedule::geometric || schedule == CoolingSchedule::adaptive) {
+        std::cout << "Temperature reduction factor lambda: " << lambda << std::endl;
+    }
     for (unsigned long int i = 0; i < number_of_iterations; ++i) {
-        simulated_annealing.perform_simulated_annealing_step(temp);
-        temp = temp / lambda;
+        simulated_annealing.perform_simulated_annealing_step(temp, attempted_worse_swaps_in_window, 
+            accepted_worse_swaps_in_window);
+
+        double progress = static_cast<double>(i + 1) / static_cast<double>(number_of_iterations);
+        switch (schedule) {
+        case CoolingSchedule::linear:
+            temp = t_start - (t_start - t_end) * progress;
+            break;
+        case CoolingSchedule::exponential:
+            temp = t_end + (t_start - t_end) * (exp(-exponential_rate * progress) - exponential_offset) / 
+                (1.0 - exponential_offset);
+            break;
+        case CoolingSchedule::logarithmic:
+            temp = t_start / (1.0 + log_factor * log(1.0 + static_cast<double>(i + 1)));
+            break;
+        default:
+            temp = temp / lambda;
+            break;
+        }
+
+        if (schedule == CoolingSchedule::adaptive && (i + 1) % adaptive_window == 0) {
+            double acceptance_rate = attempted_worse_swaps_in_window > 0 ? 
+                static_cast<double>(accepted_worse_swaps_in_window) / static_cast<double>(attempted_worse_swaps_in_window) : 1.0;
+            attempted_worse_swaps_in_window = 0;
+            accepted_worse_swaps_in_window = 0;
+            if (acceptance_rate < adaptive_frozen_rate && progress < 0.9) {
+                temp = std::min(temp * adaptive_reheat_factor, t_start);
+                lambda = pow(temp / t_end, 1.0 / static_cast<double>(number_of_iterations - i - 1));
+                number_of_reheats++;
+            }
+        }
+    }
+    if (schedule == CoolingSchedule::adaptive) {
+        std::cout << "Number of reheats: " << number_of_reheats << std::endl;
     }
     std::cout << "Total number of contacts after " << number_of_iterations << " steps of simulated annealing:\n";
     simulated_annealing.print_total_number_of_contacts();
     simulated_annealing.print_number_of_contacts_per_person();
     std::cout << "End temperature: " << temp << std::endl << std::endl << "Simulated annealing result: \n";
     simulated_annealing.print_state();
-    simulated_annealing.write_state_to_csv();
+    // Only hand out results that passed the verification
+    if (simulated_annealing.verify_state()) {
+        simulated_annealing.write_state_to_csv();
+        simulated_annealing.write_state_to_long_csv();
+        simulated_annealing.write_contact_graph_to_dot();
+    }
+    else {
+        std::cerr << "Verification failed, the result is NOT written to Data.csv, Data_long.csv and Contacts.dot!" << std::endl;
+    }
 }
\ No newline at end of file
@@ -1,11 +1,39 @@
 #pragma once
 
 #include <iostream>
+#include <string>
+#include <algorithm>
 
 #include "State.h"
 
 
-void run_random_hillclimbing_algorithm(State hill_climbing, unsigned int number_of_iterations);
+// Supported hill climbing variants:
+// "first_improvement": every iteration tries one random male and one random female swap
+//                      and executes each if it doesn't lose contacts
+// "steepest":          one iteration covers the whole neighbourhood of one random day: all
+//                      swaps on that day are evaluated and the best one is executed if it
+//                      doesn't lose contacts, picking randomly between equally good swaps so
+//                      plateaus get explored. An iteration is therefore several hundred times
+//                      as expensive as a "first_improvement" one, so use far fewer iterations.
+// Any other value throws a std::runtime_error before the algorithm starts.
+void run_random_hillclimbing_algorithm(State hill_climbing, unsigned int number_of_iterations,
+    std::string variant = "first_improvement");
 
-void run_simulated_annealing_algorithm(State simulated_annealing, unsigned int number_of_iterations);
+// Supported cooling schedules, all going from the start to the end temperature
+// over number_of_iterations:
+// "geometric":   temp is divided by a constant factor lambda every iteration
+// "linear":      temp decreases by the same amount every iteration
+// "exponential": temp = t_end + (t_start - t_end) * (exp(-k * progress) - exp(-k)) / (1 - exp(-k))
+//                with k = 10, drops much faster than geometric at the start and then
+//                spends most of the iterations at temperatures close to t_end
+// "logarithmic": temp = t_start / (1 + c * log(1 + i)), spends more time at high temps
+// "adaptive":    geometric, but reheats if almost none of the attempted worsening swaps
+//                get accepted anymore (the state is frozen) before the last 10% of the iterations
+// Any other value throws a std::runtime_error before the algorithm starts.
+// If auto_temperature is true the hard coded start and end temperatures are replaced:
+// a few hundred random swaps are sampled and the start temperature is chosen so that an
+// average contact-losing swap is accepted with 80% probability. The end temperature is chosen
+// so that even a swap losing only 1 contact is accepted with just 1% probability.
+void run_simulated_annealing_algorithm(State simulated_annealing, unsigned int number_of_iterations,
+    std::string cooling_schedule = "geometric", bool auto_temperature = false);
 